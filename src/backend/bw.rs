@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::{run_capture, Backend, Item, ItemField, ItemMetadata, ItemType};
+use crate::error::{Error, Result};
+
+/// Drives the [Bitwarden/Vaultwarden CLI](https://bitwarden.com/help/cli/) (`bw`).
+///
+/// Bitwarden items don't expose arbitrary fields the way 1Password does; the password and TOTP
+/// seed live under `login.password`/`login.totp` rather than in `fields[].value`. This backend
+/// synthesizes pseudo-fields named `password` and `otp` for those two, and maps Bitwarden's
+/// numeric custom field types onto the neutral [`ItemType`] set.
+///
+/// Bitwarden has no container that corresponds to a 1Password vault; the closest analogue that
+/// actually scopes a listing is an organization, so `--backend bw` treats the `vault` argument as
+/// an organization id rather than a name.
+pub struct BwBackend;
+
+#[async_trait]
+impl Backend for BwBackend {
+    async fn list_items(&self, vault: &str) -> Result<Vec<ItemMetadata>> {
+        let mut cmd = Command::new("bw");
+        cmd.args(["list", "items", "--organizationid", vault]);
+        let output = run_capture(cmd).await?;
+
+        Ok(serde_json::from_slice::<Vec<BwItem>>(&output)?
+            .into_iter()
+            .map(|item| ItemMetadata {
+                id: item.id,
+                title: item.name,
+            })
+            .collect())
+    }
+
+    async fn get_item(&self, id: &str) -> Result<Item> {
+        Ok(fetch_bw_item(id).await?.into_item())
+    }
+
+    async fn edit_item(&self, item: &Item) -> Result<()> {
+        // Re-fetch the item in Bitwarden's native shape so we only overwrite the fields we
+        // understand, then patch it with the (possibly updated) neutral fields.
+        let mut bw_item = fetch_bw_item(&item.id).await?;
+        bw_item.apply_item(item);
+
+        let encoded = encode(&bw_item).await?;
+
+        let mut cmd = Command::new("bw");
+        cmd.args(["edit", "item", &item.id, encoded.trim()]);
+        run_capture(cmd).await?;
+        Ok(())
+    }
+
+    async fn set_file_field(&self, id: &str, _field_label: &str, path: &Path) -> Result<()> {
+        // Bitwarden models binary credentials as item attachments rather than typed fields, so
+        // there's no per-field label to target the way `op`'s `file` fieldType has.
+        let mut cmd = Command::new("bw");
+        cmd.args(["create", "attachment", "--itemid", id, "--file"]);
+        cmd.arg(path);
+        run_capture(cmd).await?;
+        Ok(())
+    }
+}
+
+async fn fetch_bw_item(id: &str) -> Result<BwItem> {
+    let mut cmd = Command::new("bw");
+    cmd.args(["get", "item", id]);
+    let output = run_capture(cmd).await?;
+    Ok(serde_json::from_slice::<BwItem>(&output)?)
+}
+
+/// Pipe `item`'s JSON through `bw encode`, as `bw edit item` expects a base64-encoded payload.
+async fn encode(item: &BwItem) -> Result<String> {
+    let payload = serde_json::to_vec(item)?;
+
+    let mut cmd = Command::new("bw");
+    cmd.arg("encode")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut encode_cmd = cmd.spawn()?;
+
+    let mut encode_stdin = encode_cmd
+        .stdin
+        .take()
+        .expect("failed to open pipe to bw encode");
+    encode_stdin.write_all(&payload).await?;
+    drop(encode_stdin);
+
+    let output = encode_cmd.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(Error::Backend(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|_| Error::Utf8)
+}
+
+/// Reference: [Bitwarden CLI Documentation - Item object](https://bitwarden.com/help/cli/#item)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BwItem {
+    id: String,
+    name: String,
+    #[serde(default)]
+    login: Option<BwLogin>,
+    #[serde(default)]
+    fields: Option<Vec<BwField>>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct BwLogin {
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    totp: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BwField {
+    name: Option<String>,
+    value: Option<String>,
+    /// `0` = text, `1` = hidden, `2` = boolean, `3` = linked.
+    #[serde(rename = "type")]
+    field_type: u8,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+const PASSWORD_FIELD_ID: &str = "password";
+const OTP_FIELD_ID: &str = "otp";
+
+impl BwItem {
+    fn into_item(self) -> Item {
+        let mut fields = Vec::new();
+
+        if let Some(login) = &self.login {
+            if login.password.is_some() {
+                fields.push(pseudo_field(
+                    PASSWORD_FIELD_ID,
+                    ItemType::Concealed,
+                    login.password.clone(),
+                ));
+            }
+            if login.totp.is_some() {
+                fields.push(pseudo_field(
+                    OTP_FIELD_ID,
+                    ItemType::Otp,
+                    login.totp.clone(),
+                ));
+            }
+        }
+
+        for (index, field) in self.fields.iter().flatten().enumerate() {
+            fields.push(ItemField {
+                id: field.name.clone().unwrap_or_else(|| index.to_string()),
+                section: None,
+                item_type: bw_field_type(field.field_type),
+                label: field.name.clone(),
+                value: field.value.clone(),
+                reference: String::new(),
+                extra: HashMap::new(),
+            });
+        }
+
+        Item {
+            id: self.id,
+            title: self.name,
+            category: "LOGIN".to_string(),
+            sections: None,
+            fields: Some(fields),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Copy field values from a previously-converted neutral [`Item`] back into this native
+    /// Bitwarden representation.
+    fn apply_item(&mut self, item: &Item) {
+        self.name = item.title.clone();
+
+        for field in item.fields.iter().flatten() {
+            match field.id.as_str() {
+                PASSWORD_FIELD_ID => {
+                    self.login.get_or_insert_with(BwLogin::default).password = field.value.clone()
+                }
+                OTP_FIELD_ID => {
+                    self.login.get_or_insert_with(BwLogin::default).totp = field.value.clone()
+                }
+                _ => {
+                    if let Some(bw_field) = self
+                        .fields
+                        .iter_mut()
+                        .flatten()
+                        .find(|bw_field| bw_field.name.as_deref() == Some(field.id.as_str()))
+                    {
+                        bw_field.value = field.value.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn pseudo_field(id: &str, item_type: ItemType, value: Option<String>) -> ItemField {
+    ItemField {
+        id: id.to_string(),
+        section: None,
+        item_type,
+        label: Some(id.to_string()),
+        value,
+        reference: String::new(),
+        extra: HashMap::new(),
+    }
+}
+
+fn bw_field_type(field_type: u8) -> ItemType {
+    match field_type {
+        1 => ItemType::Concealed,
+        _ => ItemType::String,
+    }
+}
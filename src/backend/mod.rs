@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+
+mod bw;
+mod op;
+
+pub use bw::BwBackend;
+pub use op::OpBackend;
+
+/// Which password manager CLI to drive.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+pub enum BackendKind {
+    /// 1Password, via the `op` CLI.
+    Op,
+    /// Bitwarden or Vaultwarden, via the `bw` CLI.
+    Bw,
+}
+
+impl BackendKind {
+    pub fn build(self) -> Box<dyn Backend> {
+        match self {
+            Self::Op => Box::new(OpBackend),
+            Self::Bw => Box::new(BwBackend),
+        }
+    }
+}
+
+/// A password manager capable of listing, fetching, and updating items in a vault.
+///
+/// Each implementor is responsible for translating its CLI's native JSON shape into the neutral
+/// [`ItemMetadata`]/[`Item`] representations used by the rest of the crate. Methods are async so
+/// that many credentials can be in flight against the backend CLI at once.
+#[async_trait]
+pub trait Backend: Send + Sync {
+    /// List the items in `vault`, for matching against issuer/credential names.
+    async fn list_items(&self, vault: &str) -> Result<Vec<ItemMetadata>>;
+    /// Retrieve the full item identified by `id`.
+    async fn get_item(&self, id: &str) -> Result<Item>;
+    /// Persist `item`'s current field values back to the vault.
+    async fn edit_item(&self, item: &Item) -> Result<()>;
+    /// Assign the contents of the file at `path` to the file/binary field labeled `field_label`
+    /// on the item identified by `id`, using whatever mechanism the backend natively uses for
+    /// binary credentials (e.g. 1Password's `file` fieldType assignment, or a Bitwarden
+    /// attachment). The caller resolves `field_label` against the item's fields first, since
+    /// `op`'s assignment syntax addresses fields by label rather than by internal id.
+    async fn set_file_field(&self, id: &str, field_label: &str, path: &Path) -> Result<()>;
+}
+
+/// Run `cmd`, returning its captured stdout on success or [`Error::Backend`] with its captured
+/// stderr on a non-zero exit.
+async fn run_capture(mut cmd: Command) -> Result<Vec<u8>> {
+    let output = cmd.output().await?;
+    if !output.status.success() {
+        return Err(Error::Backend(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// A lightweight summary of a vault item, as returned by a backend's list operation.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+pub struct ItemMetadata {
+    pub id: String,
+    pub title: String,
+}
+
+/// A full vault item, in the neutral shape shared by all backends.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub sections: Option<Vec<ItemSection>>,
+    pub fields: Option<Vec<ItemField>>,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (id: {})", self.title, self.id)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemSection {
+    pub id: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemField {
+    /// If this matches a category built-in field, the type does not need to be specified.
+    pub id: String,
+    pub section: Option<ItemFieldSection>,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    pub label: Option<String>,
+    pub value: Option<String>,
+    pub reference: String,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemFieldSection {
+    pub id: String,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// Reference: [1Password CLI Documentation - Item Fields](https://developer.1password.com/docs/cli/item-fields/)
+///
+/// Bitwarden's field types are mapped onto this same set by [`BwBackend`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ItemType {
+    /// A concealed password.
+    Concealed,
+    String,
+    Email,
+    Url,
+    /// `YYYY-MM-DD`
+    Date,
+    /// `YYYYMM` or `YYYY/MM`
+    MonthYear,
+    Phone,
+    /// Accepts `otpauth://` URI
+    Otp,
+    /// An undocumented field. For example, used by the `type` field in API Credential items
+    Menu,
+    #[serde(untagged)]
+    Unknown,
+}
+
+impl ItemType {
+    /// The `fieldType` can be used with assignment statements in CLI arguments.
+    #[allow(dead_code)]
+    pub fn field_type(&self) -> &'static str {
+        match self {
+            Self::Concealed => "password",
+            Self::String => "text",
+            Self::Email => "email",
+            Self::Url => "url",
+            Self::Date => "date",
+            Self::MonthYear => "monthYear",
+            Self::Phone => "phone",
+            Self::Otp => "otp",
+            Self::Menu => "menu",
+            Self::Unknown => panic!("unrecognized field type"),
+        }
+    }
+
+    /// The `file` fieldType accepts the path to a file, and can only be used with assignment
+    /// statements.
+    pub fn file() -> &'static str {
+        "file"
+    }
+}
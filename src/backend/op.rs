@@ -0,0 +1,73 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::{run_capture, Backend, Item, ItemMetadata, ItemType};
+use crate::error::{Error, Result};
+
+/// Drives the [1Password CLI](https://developer.1password.com/docs/cli/) (`op`).
+///
+/// 1Password's own JSON shapes already match the neutral [`Item`]/[`ItemMetadata`]
+/// representations, so this backend deserializes directly into them.
+///
+/// Reference: [1Password CLI Documentation - Item JSON template](https://developer.1password.com/docs/cli/item-template-json/)
+pub struct OpBackend;
+
+#[async_trait]
+impl Backend for OpBackend {
+    async fn list_items(&self, vault: &str) -> Result<Vec<ItemMetadata>> {
+        let mut cmd = Command::new("op");
+        cmd.args(["item", "list", "--vault", vault, "--format", "json"]);
+        let output = run_capture(cmd).await?;
+        Ok(serde_json::from_slice::<Vec<ItemMetadata>>(&output)?)
+    }
+
+    async fn get_item(&self, id: &str) -> Result<Item> {
+        let mut cmd = Command::new("op");
+        cmd.args(["item", "get", id, "--format", "json"]);
+        let output = run_capture(cmd).await?;
+        Ok(serde_json::from_slice::<Item>(&output)?)
+    }
+
+    async fn edit_item(&self, item: &Item) -> Result<()> {
+        let updated_item = serde_json::to_vec(item)?;
+
+        let mut cmd = Command::new("op");
+        cmd.args(["item", "edit", &item.id])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut edit_cmd = cmd.spawn()?;
+
+        let mut edit_stdin = edit_cmd
+            .stdin
+            .take()
+            .expect("failed to open pipe to 1Password edit command");
+        edit_stdin.write_all(&updated_item).await?;
+        drop(edit_stdin);
+
+        let output = edit_cmd.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(Error::Backend(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn set_file_field(&self, id: &str, field_label: &str, path: &Path) -> Result<()> {
+        // `op item edit <item> "<label>[file]=<path>"` is the CLI's assignment syntax for
+        // binary/file fields; unlike other field types, it can't be round-tripped through the
+        // item JSON template.
+        let mut assignment = format!("{field_label}[{}]=", ItemType::file());
+        assignment.push_str(&path.to_string_lossy());
+
+        let mut cmd = Command::new("op");
+        cmd.args(["item", "edit", id, &assignment]);
+        run_capture(cmd).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+
+/// Attempt to decode `input` as Base64, trying several alphabets in turn since exported secrets
+/// come from varied tools: standard, standard without padding, URL-safe, URL-safe without
+/// padding, and finally each of those again with embedded whitespace stripped first, to tolerate
+/// line-wrapped exports in any of the four alphabets.
+pub fn decode_tolerant(input: &str) -> Option<Vec<u8>> {
+    STANDARD
+        .decode(input)
+        .or_else(|_| STANDARD_NO_PAD.decode(input))
+        .or_else(|_| URL_SAFE.decode(input))
+        .or_else(|_| URL_SAFE_NO_PAD.decode(input))
+        .or_else(|_| {
+            let stripped: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+            STANDARD
+                .decode(&stripped)
+                .or_else(|_| STANDARD_NO_PAD.decode(&stripped))
+                .or_else(|_| URL_SAFE.decode(&stripped))
+                .or_else(|_| URL_SAFE_NO_PAD.decode(&stripped))
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES: [u8; 4] = [0xfb, 0xff, 0xbe, 0xff];
+
+    #[test]
+    fn decodes_standard() {
+        assert_eq!(decode_tolerant("+/++/w=="), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn decodes_standard_no_pad() {
+        assert_eq!(decode_tolerant("+/++/w"), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn decodes_url_safe() {
+        assert_eq!(decode_tolerant("-_--_w=="), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn decodes_url_safe_no_pad() {
+        assert_eq!(decode_tolerant("-_--_w"), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn decodes_line_wrapped_standard() {
+        assert_eq!(decode_tolerant("+/++\n/w==\n"), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn decodes_line_wrapped_url_safe_no_pad() {
+        assert_eq!(decode_tolerant("-_--\n_w"), Some(BYTES.to_vec()));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(decode_tolerant("not base64 at all!!!"), None);
+    }
+}
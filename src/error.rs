@@ -0,0 +1,40 @@
+use thiserror::Error;
+
+/// Errors that can occur while reading from or writing to a vault, or while applying a single
+/// credential update.
+///
+/// A single credential's error should not abort the rest of the batch — `main` logs and
+/// continues on `Err` instead.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The backend CLI exited with a non-zero status; the message is its captured stderr.
+    #[error("backend CLI exited unsuccessfully: {0}")]
+    Backend(String),
+    /// The backend CLI could not be executed at all.
+    #[error("failed to execute backend CLI: {0}")]
+    Exec(#[from] std::io::Error),
+    /// The backend CLI's output could not be parsed as the expected JSON shape.
+    #[error("failed to parse backend CLI output: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The backend CLI's output was not valid UTF-8.
+    #[error("backend CLI output was not valid UTF-8")]
+    Utf8,
+    /// No vault item matched an issuer/credential pair.
+    #[error("no matching item found in vault {vault} for {issuer} {cred}")]
+    ItemNotFound {
+        vault: String,
+        issuer: String,
+        cred: String,
+    },
+    /// An item was found, but it had no field matching the requested selection.
+    #[error("no matching field found in item {0}")]
+    FieldNotFound(String),
+    /// An OTP credential's value was neither an `otpauth://` URI nor a valid Base32 secret.
+    #[error("OTP secret is not a valid otpauth:// URI or Base32 secret")]
+    InvalidOtpSecret,
+    /// A file credential's value was neither an existing filesystem path nor decodable Base64.
+    #[error("file credential value is neither a readable path nor valid Base64")]
+    InvalidFileCredential,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,119 +1,389 @@
-use std::{
-    fs,
-    io::Write,
-    path::PathBuf,
-    process::{Command, Stdio},
-};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
+use futures::stream::{self, StreamExt};
+use tempfile::NamedTempFile;
 
-use crate::templates::{Creds, ItemType};
+use crate::backend::{Backend, BackendKind, Item, ItemField, ItemMetadata, ItemType};
+use crate::error::{Error, Result};
+use crate::templates::{Cred, CredTarget, Creds};
 
+mod backend;
+mod encoding;
+mod error;
+mod otp;
 mod templates;
 
 #[derive(Debug, Parser)]
 struct Cli {
     /// Path to the updated credentials
     credentials: PathBuf,
-    /// 1Password vault to update credentials in
+    /// Vault to update credentials in. For `--backend op`, a 1Password vault name; for
+    /// `--backend bw`, a Bitwarden organization id (Bitwarden has no equivalent of a vault name).
     vault: String,
+    /// Which password manager CLI to drive
+    #[arg(long, value_enum, default_value_t = BackendKind::Op)]
+    backend: BackendKind,
+    /// Number of credentials to fetch and update concurrently
+    #[arg(short = 'j', long, default_value_t = default_jobs())]
+    jobs: usize,
     /// Run commands without uploading edits
     #[arg(short = 'n', long)]
     dry_run: bool,
 }
 
-fn main() {
+fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+#[tokio::main]
+async fn main() {
     let args = Cli::parse();
+    let backend = args.backend.build();
     let creds: Creds = toml::from_str(
         &fs::read_to_string(args.credentials).expect("failed to read credentials file"),
     )
     .expect("failed to parse credentials file");
 
-    for (mut item, cred) in creds.iter_templates(&args.vault) {
-        if item.fields.is_none() {
-            eprintln!("warn: item {item} has no fields, skipping");
-            continue;
-        };
+    let vault_item_list = match backend.list_items(&args.vault).await {
+        Ok(items) => items
+            .into_iter()
+            .map(|item| ItemMetadata {
+                title: item.title.to_lowercase(),
+                ..item
+            })
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            eprintln!("error: failed to list vault items: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let targets = creds.resolve_targets(&args.vault, &vault_item_list);
+    let total = targets.len();
+
+    // Group targets by resolved vault item id so that credentials sharing an item (e.g. two
+    // fields on the same API Credential item) are applied serially: applying a credential fetches
+    // the current item, sets one field, and writes the whole item back via `edit_item`, so running
+    // them concurrently against the same item would race and silently drop one of the edits.
+    // Unmatched credentials (no item to race on) are kept out of the grouping entirely.
+    let mut outcomes: Vec<(usize, Result<String>)> = Vec::with_capacity(total);
+    let mut groups: HashMap<String, Vec<(usize, CredTarget)>> = HashMap::new();
+    for (index, target) in targets.into_iter().enumerate() {
+        match target {
+            Ok(target) => groups
+                .entry(target.item.id.clone())
+                .or_default()
+                .push((index, target)),
+            Err(err) => outcomes.push((index, Err(err))),
+        }
+    }
+
+    let group_outcomes: Vec<Vec<(usize, Result<String>)>> = stream::iter(groups.into_values())
+        .map(|group| {
+            let backend = backend.as_ref();
+            async move {
+                let mut results = Vec::with_capacity(group.len());
+                for (index, target) in group {
+                    let result = apply_credential(target, backend, args.dry_run).await;
+                    results.push((index, result));
+                }
+                results
+            }
+        })
+        .buffer_unordered(args.jobs.max(1))
+        .collect()
+        .await;
+    outcomes.extend(group_outcomes.into_iter().flatten());
+
+    // The stream above completes tasks out of order; restore issuer/credential order before
+    // printing so a rerun's log lines are diffable.
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    let mut failures = 0usize;
+    for (_, outcome) in outcomes {
+        match outcome {
+            Ok(message) => println!("{message}"),
+            Err(err) => {
+                eprintln!("error: {err}");
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{failures}/{total} credential update(s) failed");
+        std::process::exit(1);
+    }
+}
+
+/// Fetch `target`'s vault item, place its credential value into the matching field, and save it
+/// unless `dry_run`. Returns the message to print on success.
+async fn apply_credential(
+    target: CredTarget,
+    backend: &dyn Backend,
+    dry_run: bool,
+) -> Result<String> {
+    let CredTarget {
+        issuer,
+        item: item_meta,
+        cred,
+    } = target;
+
+    if cred.file {
+        return apply_file_credential(item_meta, cred, backend, dry_run).await;
+    }
+
+    let mut item = backend.get_item(&item_meta.id).await?;
+
+    let field_id = select_field_id(&item, cred.field.as_deref(), cred.item_type)?;
+    let field_type = item
+        .fields
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|field| field.id == field_id)
+        .unwrap()
+        .item_type;
+
+    let value = if field_type == ItemType::Otp {
+        otp::resolve_otpauth_uri(&cred.value, &issuer, &cred.name)?
+    } else {
+        cred.value
+    };
+
+    item.fields
+        .as_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|field| field.id == field_id)
+        .unwrap()
+        .value = Some(value);
+
+    if !dry_run {
+        backend.edit_item(&item).await?;
+    }
+
+    let field_name = item
+        .fields
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|field| field.id == field_id)
+        .unwrap()
+        .label
+        .clone()
+        .unwrap_or(field_id);
+
+    Ok(format!(
+        r#"placed credential "{}" into field "{}" of vault item {item}"#,
+        cred.name, field_name
+    ))
+}
+
+/// Place a file/binary credential: `cred.value` is either a path to an existing file or an
+/// inline Base64-encoded blob, which is materialized to a temp file first since the backend CLIs
+/// only accept file fields by path.
+async fn apply_file_credential(
+    item_meta: ItemMetadata,
+    cred: Cred,
+    backend: &dyn Backend,
+    dry_run: bool,
+) -> Result<String> {
+    let selector = cred
+        .field
+        .as_deref()
+        .ok_or_else(|| Error::FieldNotFound(item_meta.title.clone()))?;
+
+    // `op`'s file-field assignment syntax addresses fields by label, not by internal id, so
+    // `selector` (which may be either, per `Cred::field`'s doc) has to be resolved against the
+    // live item rather than passed through as-is; otherwise an id value silently creates a new
+    // field instead of updating the intended one.
+    let item = backend.get_item(&item_meta.id).await?;
+    let field = find_field(&item, selector)?;
+    let field_label = field.label.clone().unwrap_or_else(|| field.id.clone());
+
+    let mut decoded_file = None;
+    let path: &Path = if Path::new(&cred.value).is_file() {
+        Path::new(&cred.value)
+    } else {
+        let bytes = encoding::decode_tolerant(&cred.value).ok_or(Error::InvalidFileCredential)?;
+        let mut file = NamedTempFile::new()?;
+        file.write_all(&bytes)?;
+        decoded_file.insert(file).path()
+    };
 
-        let concealed_fields: Vec<_> = item
-            .fields
-            .as_ref()
-            .unwrap()
+    if !dry_run {
+        backend
+            .set_file_field(&item_meta.id, &field_label, path)
+            .await?;
+    }
+
+    Ok(format!(
+        r#"placed credential "{}" into field "{}" of vault item {item}"#,
+        cred.name, field_label
+    ))
+}
+
+/// Find the field of `item` whose `id` or `label` matches `selector` (case-insensitive).
+fn find_field<'a>(item: &'a Item, selector: &str) -> Result<&'a ItemField> {
+    item.fields
+        .as_ref()
+        .ok_or_else(|| Error::FieldNotFound(item.to_string()))?
+        .iter()
+        .find(|field| {
+            field.id.eq_ignore_ascii_case(selector)
+                || field
+                    .label
+                    .as_deref()
+                    .is_some_and(|label| label.eq_ignore_ascii_case(selector))
+        })
+        .ok_or_else(|| Error::FieldNotFound(item.to_string()))
+}
+
+/// Pick which field of `item` a credential should be written to.
+///
+/// If `selector` is given, it is matched against each field's `id` or `label`
+/// (case-insensitive) and `field_type` is ignored. Otherwise, if `field_type` is given, the
+/// first field of that type is used, preferring one outside a section. With neither set, falls
+/// back to the historical heuristic: a sectionless field named `credential`, then any sectionless
+/// concealed field, then the first concealed field.
+fn select_field_id(
+    item: &Item,
+    selector: Option<&str>,
+    field_type: Option<ItemType>,
+) -> Result<String> {
+    if let Some(selector) = selector {
+        return find_field(item, selector).map(|field| field.id.to_owned());
+    }
+
+    let fields = item
+        .fields
+        .as_ref()
+        .ok_or_else(|| Error::FieldNotFound(item.to_string()))?;
+
+    if let Some(field_type) = field_type {
+        let matching: Vec<_> = fields
             .iter()
-            .filter(|field| field.item_type == ItemType::Concealed)
+            .filter(|field| field.item_type == field_type)
             .collect();
 
-        // Assume we are modifying an API credential, otherwise pick the first field not in a
-        // section, then the first field.
-        let field_id = concealed_fields
+        return matching
             .iter()
-            .find(|field| field.section.is_none() && field.id == "credential")
-            .or_else(|| {
-                concealed_fields
-                    .iter()
-                    .find(|field| field.section.is_none())
-            })
-            .or_else(|| concealed_fields.first())
-            .map(|inner| inner.id.to_owned());
-
-        if let Some(id) = &field_id {
-            item.fields
-                .as_mut()
-                .unwrap()
-                .iter_mut()
-                .find(|item| &item.id == id)
-                .unwrap()
-                .value = Some(cred.value)
-        } else {
-            eprintln!("unable to find credential field in item {}", item)
+            .find(|field| field.section.is_none())
+            .or_else(|| matching.first())
+            .map(|field| field.id.to_owned())
+            .ok_or_else(|| Error::FieldNotFound(item.to_string()));
+    }
+
+    // Assume we are modifying an API credential, otherwise pick the first field not in a
+    // section, then the first field.
+    let concealed_fields: Vec<_> = fields
+        .iter()
+        .filter(|field| field.item_type == ItemType::Concealed)
+        .collect();
+
+    concealed_fields
+        .iter()
+        .find(|field| field.section.is_none() && field.id == "credential")
+        .or_else(|| {
+            concealed_fields
+                .iter()
+                .find(|field| field.section.is_none())
+        })
+        .or_else(|| concealed_fields.first())
+        .map(|field| field.id.to_owned())
+        .ok_or_else(|| Error::FieldNotFound(item.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ItemFieldSection;
+
+    fn test_field(
+        id: &str,
+        item_type: ItemType,
+        label: Option<&str>,
+        sectioned: bool,
+    ) -> ItemField {
+        ItemField {
+            id: id.to_string(),
+            section: sectioned.then(|| ItemFieldSection {
+                id: "section1".to_string(),
+                extra: HashMap::new(),
+            }),
+            item_type,
+            label: label.map(str::to_string),
+            value: None,
+            reference: String::new(),
+            extra: HashMap::new(),
         }
+    }
 
-        // Save updated credential to 1Password
-        let updated_item = serde_json::to_vec(&item).expect("failed to serialize updated item");
-
-        if !args.dry_run {
-            let mut edit_cmd = Command::new("op")
-                .args(["item", "edit", &item.id])
-                .stdin(Stdio::piped())
-                .stdout(Stdio::null())
-                .spawn()
-                .expect("failed to spawn 1Password edit command");
-
-            let mut edit_stdin = edit_cmd
-                .stdin
-                .take()
-                .expect("failed to open pipe to 1Password edit command");
-            std::thread::spawn(move || {
-                edit_stdin
-                    .write_all(updated_item.as_slice())
-                    .expect("failed to write updated item to pipe")
-            });
-
-            let status = edit_cmd.wait().expect("1Password edit command failed");
-            if !status.success() {
-                panic!("1Password CLI unexpectedly exited: {status}")
-            }
+    fn test_item(fields: Vec<ItemField>) -> Item {
+        Item {
+            id: "item1".to_string(),
+            title: "Test Item".to_string(),
+            category: "LOGIN".to_string(),
+            sections: None,
+            fields: Some(fields),
+            extra: HashMap::new(),
         }
+    }
 
-        let field_name = if let Some(label) = item
-            .fields
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|field| &field.id == field_id.as_ref().unwrap())
-            .unwrap()
-            .label
-            .as_ref()
-        {
-            label
-        } else {
-            &field_id.unwrap()
-        };
-
-        println!(
-            r#"placed credential "{}" into field "{}" of vault item {item}"#,
-            cred.name, field_name
+    #[test]
+    fn select_field_id_selector_matches_by_id() {
+        let item = test_item(vec![
+            test_field("password", ItemType::Concealed, Some("Password"), false),
+            test_field("api_key", ItemType::Concealed, Some("API Key"), false),
+        ]);
+
+        assert_eq!(
+            select_field_id(&item, Some("api_key"), None).unwrap(),
+            "api_key"
+        );
+    }
+
+    #[test]
+    fn select_field_id_selector_matches_by_label_case_insensitive() {
+        let item = test_item(vec![test_field(
+            "f1",
+            ItemType::Concealed,
+            Some("API Key"),
+            false,
+        )]);
+
+        assert_eq!(select_field_id(&item, Some("api key"), None).unwrap(), "f1");
+    }
+
+    #[test]
+    fn select_field_id_type_only_prefers_sectionless_field() {
+        let item = test_item(vec![
+            test_field("in_section", ItemType::Otp, Some("TOTP"), true),
+            test_field("sectionless", ItemType::Otp, Some("TOTP 2"), false),
+        ]);
+
+        assert_eq!(
+            select_field_id(&item, None, Some(ItemType::Otp)).unwrap(),
+            "sectionless"
         );
-        continue;
+    }
+
+    #[test]
+    fn select_field_id_legacy_heuristic_falls_back_to_sectionless_concealed_field() {
+        // No field is named "credential", so the heuristic should fall back to the first
+        // sectionless concealed field rather than the sectioned one.
+        let item = test_item(vec![
+            test_field("in_section", ItemType::Concealed, None, true),
+            test_field("sectionless", ItemType::Concealed, None, false),
+        ]);
+
+        assert_eq!(select_field_id(&item, None, None).unwrap(), "sectionless");
     }
 }
@@ -0,0 +1,104 @@
+use crate::error::{Error, Result};
+
+/// RFC 4648 Base32 alphabet.
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Build (or pass through) an `otpauth://` URI for an [`ItemType::Otp`](crate::backend::ItemType)
+/// field.
+///
+/// If `value` is already an `otpauth://` URI it is used as-is. Otherwise it's treated as a Base32
+/// TOTP secret: whitespace and `=` padding are stripped, the result is validated against the
+/// RFC 4648 Base32 alphabet and uppercased, then wrapped in a standard TOTP URI labeled
+/// `{issuer}:{name}`.
+pub fn resolve_otpauth_uri(value: &str, issuer: &str, name: &str) -> Result<String> {
+    if value.starts_with("otpauth://") {
+        return Ok(value.to_string());
+    }
+
+    let secret = normalize_base32_secret(value)?;
+    let issuer = percent_encode(issuer);
+    Ok(format!(
+        "otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits=6&period=30",
+        percent_encode(name),
+    ))
+}
+
+/// Strip whitespace and `=` padding, uppercase, and validate against the RFC 4648 Base32
+/// alphabet.
+fn normalize_base32_secret(secret: &str) -> Result<String> {
+    let cleaned: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+
+    if cleaned.is_empty() || !cleaned.bytes().all(|byte| BASE32_ALPHABET.contains(&byte)) {
+        return Err(Error::InvalidOtpSecret);
+    }
+
+    Ok(cleaned)
+}
+
+/// Percent-encode the handful of characters that would otherwise break an `otpauth://` URI
+/// component; issuer/account names may contain spaces or colons.
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_through_existing_otpauth_uri() {
+        let uri = "otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP";
+        assert_eq!(resolve_otpauth_uri(uri, "Example", "alice").unwrap(), uri);
+    }
+
+    #[test]
+    fn builds_uri_from_base32_secret() {
+        let uri = resolve_otpauth_uri("jbsw y3dp ehpk 3pxp", "My Co", "alice").unwrap();
+        assert_eq!(
+            uri,
+            "otpauth://totp/My%20Co:alice?secret=JBSWY3DPEHPK3PXP&issuer=My%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn strips_whitespace_and_padding_before_validating() {
+        let uri = resolve_otpauth_uri("jbsw y3dp ehpk 3pxp ===", "issuer", "name").unwrap();
+        assert!(uri.contains("secret=JBSWY3DPEHPK3PXP"));
+    }
+
+    #[test]
+    fn rejects_non_base32_secret() {
+        assert!(matches!(
+            resolve_otpauth_uri("not-a-valid-secret!", "issuer", "name"),
+            Err(Error::InvalidOtpSecret)
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_secret() {
+        assert!(matches!(
+            resolve_otpauth_uri("   ", "issuer", "name"),
+            Err(Error::InvalidOtpSecret)
+        ));
+    }
+
+    #[test]
+    fn percent_encodes_issuer_and_name() {
+        let uri = resolve_otpauth_uri("JBSWY3DPEHPK3PXP", "My Co: Inc", "a b").unwrap();
+        assert!(uri.contains("My%20Co%3A%20Inc:a%20b"));
+        assert!(uri.contains("issuer=My%20Co%3A%20Inc"));
+    }
+}